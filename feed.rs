@@ -0,0 +1,526 @@
+use crate::{AppError, NewsArticle};
+use chrono::prelude::*;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+// Maximum length of a description before we truncate it with an ellipsis.
+const DESCRIPTION_LEN: usize = 200;
+
+// The feed formats we know how to parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FeedKind {
+    Rss,
+    Atom,
+    Json,
+}
+
+// A format-detecting feed parser that normalizes RSS 2.0, Atom and JSON Feed
+// documents into our internal `NewsArticle` representation.
+pub struct FeedParser<'a> {
+    source_name: &'a str,
+}
+
+impl<'a> FeedParser<'a> {
+    pub fn new(source_name: &'a str) -> Self {
+        FeedParser { source_name }
+    }
+
+    // Detect the document type and dispatch to the matching parser.
+    pub fn parse(&self, body: &str) -> Result<Vec<NewsArticle>, AppError> {
+        match detect_kind(body) {
+            FeedKind::Json => self.parse_json(body),
+            FeedKind::Atom => self.parse_xml(body, FeedKind::Atom),
+            FeedKind::Rss => self.parse_xml(body, FeedKind::Rss),
+        }
+    }
+
+    // RSS 2.0 (`<item>`) and Atom (`<entry>`) share an XML reader; the element
+    // names and the place the link lives are the only differences.
+    fn parse_xml(&self, body: &str, kind: FeedKind) -> Result<Vec<NewsArticle>, AppError> {
+        let entry_tag: &[u8] = if kind == FeedKind::Atom { b"entry" } else { b"item" };
+
+        let mut reader = Reader::from_str(body);
+        reader.config_mut().trim_text(true);
+
+        let mut articles = Vec::new();
+        let mut buf = Vec::new();
+        let mut current: Option<EntryBuilder> = None;
+        // The name of the element whose text we are currently collecting.
+        let mut field: Option<Vec<u8>> = None;
+
+        loop {
+            match reader
+                .read_event_into(&mut buf)
+                .map_err(|e| AppError::ParsingError(e.to_string()))?
+            {
+                Event::Start(ref e) => {
+                    let name = e.local_name().as_ref().to_vec();
+                    if name == entry_tag {
+                        current = Some(EntryBuilder::default());
+                    } else if let Some(entry) = current.as_mut() {
+                        // Atom keeps the article URL in `<link href="...">`.
+                        if kind == FeedKind::Atom && name == b"link" {
+                            if let Some(href) = alternate_href(e) {
+                                entry.link = href;
+                            }
+                        }
+                        field = Some(name);
+                    }
+                }
+                Event::Empty(ref e) => {
+                    // Self-closing Atom links, e.g. `<link href="..." />`.
+                    if kind == FeedKind::Atom && e.local_name().as_ref() == b"link" {
+                        if let Some(entry) = current.as_mut() {
+                            if let Some(href) = alternate_href(e) {
+                                entry.link = href;
+                            }
+                        }
+                    }
+                }
+                Event::Text(ref e) => {
+                    if let (Some(entry), Some(name)) = (current.as_mut(), field.as_ref()) {
+                        let text = e
+                            .unescape()
+                            .map_err(|err| AppError::ParsingError(err.to_string()))?
+                            .into_owned();
+                        entry.push_field(name, kind, text);
+                    }
+                }
+                Event::CData(ref e) => {
+                    if let (Some(entry), Some(name)) = (current.as_mut(), field.as_ref()) {
+                        let text = String::from_utf8_lossy(e.as_ref()).into_owned();
+                        entry.push_field(name, kind, text);
+                    }
+                }
+                Event::End(ref e) => {
+                    let name = e.local_name().as_ref().to_vec();
+                    if name == entry_tag {
+                        if let Some(entry) = current.take() {
+                            if let Some(article) = entry.build(self.source_name) {
+                                articles.push(article);
+                            }
+                        }
+                    } else if field.as_deref() == Some(name.as_slice()) {
+                        field = None;
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        Ok(articles)
+    }
+
+    // JSON Feed 1.1: `items[]` with `id`, `url`, `content_html` and
+    // `date_published`.
+    fn parse_json(&self, body: &str) -> Result<Vec<NewsArticle>, AppError> {
+        let feed: JsonFeed =
+            serde_json::from_str(body).map_err(|e| AppError::ParsingError(e.to_string()))?;
+
+        let articles = feed
+            .items
+            .into_iter()
+            .map(|item| {
+                let link = item.url.or(item.id).unwrap_or_default();
+                let body = decode_entities(
+                    &item.content_text.or(item.content_html).unwrap_or_default(),
+                );
+                let pub_date = item.date_published.unwrap_or_default();
+                NewsArticle {
+                    id: article_id(&link),
+                    title: decode_entities(&item.title.unwrap_or_default()),
+                    link,
+                    description: truncate(&strip_markup(&body)),
+                    source: self.source_name.to_string(),
+                    timestamp: parse_date(&pub_date),
+                    pub_date,
+                    hidden: false,
+                }
+            })
+            .collect();
+
+        Ok(articles)
+    }
+}
+
+// Accumulates the fields of a single RSS/Atom entry as we stream the document.
+#[derive(Default)]
+struct EntryBuilder {
+    title: String,
+    link: String,
+    body: String,
+    pub_date: String,
+}
+
+impl EntryBuilder {
+    fn push_field(&mut self, name: &[u8], kind: FeedKind, text: String) {
+        match name {
+            b"title" => self.title = text,
+            // RSS links are plain text inside `<link>`; Atom links come from
+            // the `href` attribute handled in `parse_xml`.
+            b"link" if kind == FeedKind::Rss => self.link = text,
+            b"description" | b"summary" | b"content" | b"encoded" => {
+                if self.body.is_empty() {
+                    self.body = text;
+                }
+            }
+            b"pubDate" | b"published" | b"updated" => {
+                if self.pub_date.is_empty() {
+                    self.pub_date = text;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn build(self, source_name: &str) -> Option<NewsArticle> {
+        if self.title.is_empty() && self.link.is_empty() {
+            return None;
+        }
+        let timestamp = parse_date(&self.pub_date);
+        Some(NewsArticle {
+            id: article_id(&self.link),
+            title: self.title,
+            link: self.link,
+            description: truncate(&strip_markup(&self.body)),
+            source: source_name.to_string(),
+            pub_date: self.pub_date,
+            timestamp,
+            hidden: false,
+        })
+    }
+}
+
+// JSON Feed 1.1 item. Only the fields we care about are modelled.
+#[derive(Deserialize)]
+struct JsonFeed {
+    #[serde(default)]
+    items: Vec<JsonItem>,
+}
+
+#[derive(Deserialize)]
+struct JsonItem {
+    id: Option<String>,
+    url: Option<String>,
+    title: Option<String>,
+    content_text: Option<String>,
+    content_html: Option<String>,
+    date_published: Option<String>,
+}
+
+// Peek at the first non-whitespace bytes to classify the document: a `{`
+// means JSON Feed, otherwise the root element (the first tag that is not a
+// declaration, comment or doctype) decides between Atom and RSS.
+fn detect_kind(body: &str) -> FeedKind {
+    let trimmed = body.trim_start();
+    if trimmed.starts_with('{') {
+        return FeedKind::Json;
+    }
+    let root = trimmed
+        .match_indices('<')
+        .map(|(i, _)| &trimmed[i + 1..])
+        .find(|rest| !rest.starts_with('?') && !rest.starts_with('!'));
+    match root {
+        Some(rest) if rest.starts_with("feed") => FeedKind::Atom,
+        _ => FeedKind::Rss,
+    }
+}
+
+// The `href` of an Atom `<link>`, but only for the alternate representation:
+// `rel="alternate"` or no `rel` at all (which defaults to alternate). This
+// skips `rel="self"`/`rel="edit"` links that would otherwise clobber the real
+// story URL when an entry carries several `<link>` tags.
+fn alternate_href(e: &quick_xml::events::BytesStart) -> Option<String> {
+    match attr(e, b"rel") {
+        Some(rel) if rel != "alternate" => None,
+        _ => attr(e, b"href"),
+    }
+}
+
+// Read a named attribute off a start tag, returning its unescaped value.
+fn attr(e: &quick_xml::events::BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes().flatten().find(|a| a.key.as_ref() == key).map(|a| {
+        a.unescape_value()
+            .map(|v| v.into_owned())
+            .unwrap_or_else(|_| String::from_utf8_lossy(&a.value).into_owned())
+    })
+}
+
+// Strip HTML tags so descriptions render as plain text.
+fn strip_markup(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut in_tag = false;
+    for c in input.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Decode HTML entities in JSON Feed content. The RSS/Atom path gets this for
+// free from quick_xml's `.unescape()`, but JSON bodies arrive raw, so decode
+// named, decimal and hexadecimal entities before markup stripping.
+fn decode_entities(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let after = &rest[amp..];
+        if let Some(semi) = after.find(';') {
+            if let Some(ch) = decode_entity(&after[1..semi]) {
+                out.push(ch);
+                rest = &after[semi + 1..];
+                continue;
+            }
+        }
+        // Not a recognized entity; keep the ampersand literally.
+        out.push('&');
+        rest = &after[1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+// Resolve a single entity name (without the `&`/`;`) to its character.
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{00A0}'),
+        _ => {
+            if let Some(hex) = entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = entity.strip_prefix('#') {
+                dec.parse::<u32>().ok().and_then(char::from_u32)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// Truncate to `DESCRIPTION_LEN` characters, matching the legacy behaviour of
+// appending an ellipsis.
+fn truncate(input: &str) -> String {
+    input.chars().take(DESCRIPTION_LEN).collect::<String>() + "..."
+}
+
+// Stable article identifier: the hex SHA-256 of the link. Used to key hidden
+// state that must survive the cache being rebuilt every refresh.
+pub fn article_id(link: &str) -> String {
+    let digest = Sha256::digest(link.as_bytes());
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Parse a feed date, trying RFC2822 (RSS) then RFC3339 (Atom/JSON Feed) and
+// falling back to "now" so articles without a usable date still sort sanely.
+pub fn parse_date(raw: &str) -> i64 {
+    if let Ok(dt) = DateTime::parse_from_rfc2822(raw) {
+        return dt.timestamp();
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(raw) {
+        return dt.timestamp();
+    }
+    Utc::now().timestamp()
+}
+
+// Title/description reused by the syndication output for our own meta-feed.
+const FEED_TITLE: &str = "Cryptocurrency News Aggregator";
+const FEED_DESCRIPTION: &str = "Aggregated cryptocurrency news from multiple sources";
+
+// Render the cached articles as an RSS 2.0 channel.
+pub fn to_rss(articles: &[NewsArticle], link: &str, last_build: &str) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<rss version=\"2.0\">\n<channel>\n");
+    out.push_str(&format!("<title>{}</title>\n", escape(FEED_TITLE)));
+    out.push_str(&format!("<link>{}</link>\n", escape(link)));
+    out.push_str(&format!("<description>{}</description>\n", escape(FEED_DESCRIPTION)));
+    out.push_str(&format!("<lastBuildDate>{}</lastBuildDate>\n", escape(last_build)));
+    for a in articles {
+        out.push_str("<item>\n");
+        out.push_str(&format!("<title>{}</title>\n", escape(&a.title)));
+        out.push_str(&format!("<link>{}</link>\n", escape(&a.link)));
+        out.push_str(&format!("<description>{}</description>\n", escape(&a.description)));
+        out.push_str(&format!("<author>{}</author>\n", escape(&a.source)));
+        out.push_str(&format!("<category>{}</category>\n", escape(&a.source)));
+        out.push_str(&format!("<pubDate>{}</pubDate>\n", escape(&a.pub_date)));
+        out.push_str("</item>\n");
+    }
+    out.push_str("</channel>\n</rss>\n");
+    out
+}
+
+// Render the cached articles as an Atom feed.
+pub fn to_atom(articles: &[NewsArticle], link: &str, updated: &str) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    out.push_str(&format!("<title>{}</title>\n", escape(FEED_TITLE)));
+    out.push_str(&format!("<subtitle>{}</subtitle>\n", escape(FEED_DESCRIPTION)));
+    out.push_str(&format!("<link href=\"{}\"/>\n", escape(link)));
+    out.push_str(&format!("<id>{}</id>\n", escape(link)));
+    out.push_str(&format!("<updated>{}</updated>\n", escape(updated)));
+    for a in articles {
+        out.push_str("<entry>\n");
+        out.push_str(&format!("<title>{}</title>\n", escape(&a.title)));
+        out.push_str(&format!("<link href=\"{}\"/>\n", escape(&a.link)));
+        out.push_str(&format!("<id>{}</id>\n", escape(&a.link)));
+        out.push_str(&format!("<author><name>{}</name></author>\n", escape(&a.source)));
+        out.push_str(&format!("<category term=\"{}\"/>\n", escape(&a.source)));
+        out.push_str(&format!("<updated>{}</updated>\n", escape(&to_rfc3339(a.timestamp))));
+        out.push_str(&format!("<summary>{}</summary>\n", escape(&a.description)));
+        out.push_str("</entry>\n");
+    }
+    out.push_str("</feed>\n");
+    out
+}
+
+// Render the cached articles as JSON Feed 1.1.
+pub fn to_json_feed(articles: &[NewsArticle], feed_url: &str) -> String {
+    let items: Vec<serde_json::Value> = articles
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "id": a.link,
+                "url": a.link,
+                "title": a.title,
+                "content_text": a.description,
+                "date_published": to_rfc3339(a.timestamp),
+            })
+        })
+        .collect();
+
+    let feed = serde_json::json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": FEED_TITLE,
+        "description": FEED_DESCRIPTION,
+        "feed_url": feed_url,
+        "items": items,
+    });
+
+    serde_json::to_string_pretty(&feed).unwrap_or_else(|_| "{}".to_string())
+}
+
+// Format a Unix timestamp as RFC3339 for Atom/JSON Feed output.
+fn to_rfc3339(timestamp: i64) -> String {
+    Utc.timestamp_opt(timestamp, 0)
+        .single()
+        .unwrap_or_else(Utc::now)
+        .to_rfc3339()
+}
+
+// Escape the five XML predefined entities in element text and attributes.
+fn escape(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01T00:00:00Z as a Unix timestamp.
+    const EPOCH_2024: i64 = 1_704_067_200;
+
+    #[test]
+    fn parse_date_reads_rfc2822() {
+        assert_eq!(parse_date("Mon, 01 Jan 2024 00:00:00 +0000"), EPOCH_2024);
+    }
+
+    #[test]
+    fn parse_date_reads_rfc3339() {
+        assert_eq!(parse_date("2024-01-01T00:00:00+00:00"), EPOCH_2024);
+        assert_eq!(parse_date("2024-01-01T00:00:00Z"), EPOCH_2024);
+    }
+
+    #[test]
+    fn parse_date_falls_back_without_panicking() {
+        // Unparseable dates fall back to "now", which is always positive.
+        assert!(parse_date("not a date") > 0);
+    }
+
+    #[test]
+    fn decode_entities_handles_named_and_numeric() {
+        assert_eq!(decode_entities("AT&amp;T"), "AT&T");
+        assert_eq!(decode_entities("&lt;tag&gt;"), "<tag>");
+        assert_eq!(decode_entities("it&#39;s &#x27;quoted&#x27;"), "it's 'quoted'");
+        assert_eq!(decode_entities("a&nbsp;b"), "a\u{00A0}b");
+        // A lone ampersand that is not an entity is preserved verbatim.
+        assert_eq!(decode_entities("Tom & Jerry"), "Tom & Jerry");
+    }
+
+    #[test]
+    fn detects_and_parses_rss() {
+        let xml = r#"<rss version="2.0"><channel><item>
+            <title>Hello &amp; Hi</title>
+            <link>http://example.com/1</link>
+            <description><![CDATA[<p>Body text</p>]]></description>
+            <pubDate>Mon, 01 Jan 2024 00:00:00 +0000</pubDate>
+        </item></channel></rss>"#;
+
+        let articles = FeedParser::new("Src").parse(xml).unwrap();
+        assert_eq!(articles.len(), 1);
+        let a = &articles[0];
+        assert_eq!(a.title, "Hello & Hi");
+        assert_eq!(a.link, "http://example.com/1");
+        assert_eq!(a.description, "Body text...");
+        assert_eq!(a.timestamp, EPOCH_2024);
+        assert_eq!(a.source, "Src");
+    }
+
+    #[test]
+    fn detects_atom_and_prefers_alternate_link() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom"><entry>
+            <title>Atom Post</title>
+            <link rel="self" href="http://example.com/self"/>
+            <link rel="alternate" href="http://example.com/story"/>
+            <summary>Summary text</summary>
+            <updated>2024-01-01T00:00:00Z</updated>
+        </entry></feed>"#;
+
+        let articles = FeedParser::new("Src").parse(xml).unwrap();
+        assert_eq!(articles.len(), 1);
+        let a = &articles[0];
+        assert_eq!(a.title, "Atom Post");
+        // The self link must not clobber the alternate story URL.
+        assert_eq!(a.link, "http://example.com/story");
+        assert_eq!(a.timestamp, EPOCH_2024);
+    }
+
+    #[test]
+    fn detects_json_feed_and_decodes_title() {
+        let json = r#"{"version":"https://jsonfeed.org/version/1.1","items":[
+            {"id":"http://example.com/j","title":"JSON &amp; Feed",
+             "content_text":"hello","date_published":"2024-01-01T00:00:00Z"}
+        ]}"#;
+
+        let articles = FeedParser::new("Src").parse(json).unwrap();
+        assert_eq!(articles.len(), 1);
+        let a = &articles[0];
+        assert_eq!(a.title, "JSON & Feed");
+        assert_eq!(a.link, "http://example.com/j");
+        assert_eq!(a.timestamp, EPOCH_2024);
+    }
+}
@@ -1,5 +1,6 @@
 use actix_files::Files;
-use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Result};
+use actix_web::http::header;
+use actix_web::{middleware, web, App, HttpRequest, HttpResponse, HttpServer, Result};
 use chrono::prelude::*;
 use parking_lot::RwLock;
 use reqwest::Client;
@@ -9,6 +10,22 @@ use std::time::Duration;
 use tera::{Context, Tera};
 use thiserror::Error;
 
+mod feed;
+mod search;
+mod store;
+mod trending;
+
+use feed::FeedParser;
+use search::SearchIndex;
+use store::HiddenStore;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+
 // Define our news sources
 const NEWS_SOURCES: &[(&str, &str)] = &[
     ("CoinDesk", "https://www.coindesk.com/arc/outboundfeeds/rss/"),
@@ -19,6 +36,15 @@ const NEWS_SOURCES: &[(&str, &str)] = &[
 // How frequently to refresh news (in seconds)
 const REFRESH_INTERVAL: u64 = 300; // 5 minutes
 
+// Number of trending terms surfaced by the API and sidebar
+const TRENDING_LIMIT: usize = 10;
+
+// Capacity of the new-article broadcast channel feeding the SSE endpoint
+const STREAM_CAPACITY: usize = 256;
+
+// On-disk location of the dismissed-article set
+const HIDDEN_STORE_PATH: &str = "hidden.json";
+
 // Error types
 #[derive(Error, Debug)]
 enum AppError {
@@ -32,12 +58,15 @@ enum AppError {
 // News article model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct NewsArticle {
+    id: String, // Stable identifier: SHA-256 of the link
     title: String,
     link: String,
     description: String,
     source: String,
     pub_date: String,
     timestamp: i64, // For sorting
+    #[serde(default)]
+    hidden: bool, // Computed per request for the template
 }
 
 impl NewsArticle {
@@ -59,76 +88,45 @@ impl NewsArticle {
 #[derive(Deserialize)]
 struct SearchQuery {
     q: Option<String>,
+    show_hidden: Option<bool>,
+}
+
+// Request body for the hide/unhide endpoints.
+#[derive(Deserialize)]
+struct HideRequest {
+    id: String,
 }
 
 // Application state
 struct AppState {
     news_cache: RwLock<Vec<NewsArticle>>,
+    search_index: RwLock<SearchIndex>,
+    trending: RwLock<HashMap<String, VecDeque<(i64, u32)>>>,
+    // Broadcasts newly discovered articles (with a monotonic event id) to SSE
+    // subscribers.
+    new_articles: broadcast::Sender<(u64, NewsArticle)>,
+    next_event_id: AtomicU64,
+    // Bounded backlog of recently broadcast articles so a client reconnecting
+    // with `Last-Event-ID` can replay what it missed.
+    recent: RwLock<VecDeque<(u64, NewsArticle)>>,
+    hidden: HiddenStore,
+    // Unix time the cache was last swapped; drives cheap ETag computation.
+    last_updated: AtomicI64,
     templates: Tera,
 }
 
-// Helper to extract content from RSS XML
-fn extract_text(xml: &str, tag: &str) -> Result<String, AppError> {
-    let start_tag = format!("<{}>", tag);
-    let end_tag = format!("</{}>", tag);
-    
-    match (xml.find(&start_tag), xml.find(&end_tag)) {
-        (Some(start), Some(end)) => {
-            let start_pos = start + start_tag.len();
-            if start_pos < end {
-                Ok(xml[start_pos..end].to_string())
-            } else {
-                Err(AppError::ParsingError(format!("Invalid tag positions for {}", tag)))
-            }
-        }
-        _ => Err(AppError::ParsingError(format!("Tag not found: {}", tag))),
-    }
-}
-
-// Parse RSS feed
-async fn parse_rss(client: &Client, source_name: &str, url: &str) -> Result<Vec<NewsArticle>, AppError> {
+// Fetch and parse a single feed, auto-detecting its format (RSS/Atom/JSON Feed)
+async fn fetch_feed(client: &Client, source_name: &str, url: &str) -> Result<Vec<NewsArticle>, AppError> {
     let response = client.get(url).send().await?.text().await?;
-    
-    let mut articles = Vec::new();
-    
-    // Very basic RSS parser - for production use a proper RSS parser crate
-    let items: Vec<&str> = response.split("<item>").skip(1).collect();
-    
-    for item in items {
-        if let (Ok(title), Ok(link), Ok(description), Ok(pub_date)) = (
-            extract_text(item, "title"),
-            extract_text(item, "link"),
-            extract_text(item, "description"),
-            extract_text(item, "pubDate"),
-        ) {
-            // Parse the date
-            let timestamp = match DateTime::parse_from_rfc2822(&pub_date) {
-                Ok(dt) => dt.timestamp(),
-                Err(_) => Utc::now().timestamp(), // Fallback to current time
-            };
-            
-            let article = NewsArticle {
-                title,
-                link,
-                description: description.chars().take(200).collect::<String>() + "...",
-                source: source_name.to_string(),
-                pub_date,
-                timestamp,
-            };
-            
-            articles.push(article);
-        }
-    }
-    
-    Ok(articles)
+    FeedParser::new(source_name).parse(&response)
 }
 
 // Fetch news from all sources
 async fn fetch_all_news(client: &Client) -> Vec<NewsArticle> {
     let mut all_articles = Vec::new();
-    
+
     for (source_name, url) in NEWS_SOURCES {
-        match parse_rss(client, source_name, url).await {
+        match fetch_feed(client, source_name, url).await {
             Ok(mut articles) => all_articles.append(&mut articles),
             Err(e) => eprintln!("Error fetching from {}: {:?}", source_name, e),
         }
@@ -147,13 +145,51 @@ async fn news_refresher(app_state: Arc<AppState>) {
     loop {
         println!("Refreshing news...");
         let articles = fetch_all_news(&client).await;
-        
+
+        // Fold the new batch into the trending keyword buckets.
+        trending::update(&app_state.trending, &articles, Utc::now().timestamp());
+
+        // Rebuild the search index from the fresh batch.
+        let index = SearchIndex::build(&articles);
+
+        // Diff against the previous cache (keyed by link) to find genuinely
+        // new articles, and push them to any SSE subscribers.
+        {
+            let previous: HashSet<String> = app_state
+                .news_cache
+                .read()
+                .iter()
+                .map(|a| a.link.clone())
+                .collect();
+            for article in articles.iter().filter(|a| !previous.contains(&a.link)) {
+                // Hold the backlog lock across the id allocation, the push and
+                // the broadcast so an SSE handler snapshotting `recent` +
+                // subscribing under the read lock sees each article in exactly
+                // one of the two paths, never both and never neither.
+                let mut recent = app_state.recent.write();
+                let id = app_state.next_event_id.fetch_add(1, Ordering::Relaxed);
+                recent.push_back((id, article.clone()));
+                while recent.len() > STREAM_CAPACITY {
+                    recent.pop_front();
+                }
+                // A send error just means nobody is currently listening.
+                let _ = app_state.new_articles.send((id, article.clone()));
+            }
+        }
+
         // Update the cache
         {
             let mut cache = app_state.news_cache.write();
             *cache = articles;
         }
-        
+        {
+            let mut search_index = app_state.search_index.write();
+            *search_index = index;
+        }
+        app_state
+            .last_updated
+            .store(Utc::now().timestamp(), Ordering::Relaxed);
+
         // Wait for the next refresh interval
         tokio::time::sleep(Duration::from_secs(REFRESH_INTERVAL)).await;
     }
@@ -162,24 +198,25 @@ async fn news_refresher(app_state: Arc<AppState>) {
 // Handler for the home page
 async fn index(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
     // Get the query parameters
-    let query = web::Query::<SearchQuery>::from_query(req.query_string()).unwrap_or(web::Query(SearchQuery { q: None }));
+    let query = web::Query::<SearchQuery>::from_query(req.query_string()).unwrap_or(web::Query(SearchQuery { q: None, show_hidden: None }));
     let search_term = query.q.clone().unwrap_or_default();
-    
-    // Get all articles
-    let all_articles = data.news_cache.read().clone();
-    
-    // Filter articles based on search term
-    let filtered_articles: Vec<NewsArticle> = all_articles
-        .into_iter()
-        .filter(|article| article.matches_search(&search_term))
-        .collect();
-    
+
+    let etag = cache_etag(data.last_updated.load(Ordering::Relaxed), data.hidden.generation(), req.query_string());
+    if if_none_match(&req, &etag) {
+        return Ok(HttpResponse::NotModified().insert_header((header::ETAG, etag)).finish());
+    }
+
+    // Rank articles against the search term using the inverted index.
+    let ranked = data.search_index.read().search(&search_term);
+    let filtered_articles = apply_hidden(ranked, &data.hidden, query.show_hidden.unwrap_or(false));
+
     let mut context = Context::new();
     context.insert("articles", &filtered_articles);
     context.insert("search_term", &search_term);
     context.insert("last_updated", &Utc::now().to_rfc2822());
     context.insert("article_count", &filtered_articles.len());
     context.insert("has_search", &!search_term.is_empty());
+    context.insert("trending", &trending::top(&data.trending, TRENDING_LIMIT, Utc::now().timestamp()));
     
     let rendered = data.templates.render("index.html", &context)
         .unwrap_or_else(|e| {
@@ -187,25 +224,199 @@ async fn index(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpR
             "Error rendering template".to_string()
         });
     
-    Ok(HttpResponse::Ok().content_type("text/html").body(rendered))
+    Ok(HttpResponse::Ok()
+        .content_type("text/html")
+        .insert_header((header::ETAG, etag))
+        .body(rendered))
 }
 
 // API endpoint to get news as JSON, with search
 async fn api_news(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
     // Get the query parameters
-    let query = web::Query::<SearchQuery>::from_query(req.query_string()).unwrap_or(web::Query(SearchQuery { q: None }));
+    let query = web::Query::<SearchQuery>::from_query(req.query_string()).unwrap_or(web::Query(SearchQuery { q: None, show_hidden: None }));
     let search_term = query.q.clone().unwrap_or_default();
-    
-    // Get all articles
-    let all_articles = data.news_cache.read().clone();
-    
-    // Filter articles based on search term
-    let filtered_articles: Vec<NewsArticle> = all_articles
-        .into_iter()
-        .filter(|article| article.matches_search(&search_term))
-        .collect();
-    
-    Ok(HttpResponse::Ok().json(filtered_articles))
+
+    let etag = cache_etag(data.last_updated.load(Ordering::Relaxed), data.hidden.generation(), req.query_string());
+    if if_none_match(&req, &etag) {
+        return Ok(HttpResponse::NotModified().insert_header((header::ETAG, etag)).finish());
+    }
+
+    // Rank articles against the search term using the inverted index.
+    let ranked = data.search_index.read().search(&search_term);
+    let filtered_articles = apply_hidden(ranked, &data.hidden, query.show_hidden.unwrap_or(false));
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::ETAG, etag))
+        .json(filtered_articles))
+}
+
+// Format a single article as an SSE `article` event frame.
+fn sse_frame(id: u64, article: &NewsArticle) -> web::Bytes {
+    let json = serde_json::to_string(article).unwrap_or_default();
+    web::Bytes::from(format!("id: {}\nevent: article\ndata: {}\n\n", id, json))
+}
+
+// SSE endpoint that holds the connection open and pushes newly discovered
+// articles as they arrive, optionally filtered by `?q=`. A client reconnecting
+// with `Last-Event-ID` first receives the backlog it missed, then the live
+// stream, so no article published during the gap is lost.
+async fn api_stream(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let query = web::Query::<SearchQuery>::from_query(req.query_string())
+        .unwrap_or(web::Query(SearchQuery { q: None, show_hidden: None }));
+    let search_term = query.q.clone().unwrap_or_default();
+
+    // The last event id the client already saw, if it is reconnecting.
+    let last_seen = req
+        .headers()
+        .get(header::LAST_EVENT_ID)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok());
+
+    // Snapshot the backlog and subscribe under the backlog read lock so the
+    // two cannot interleave with a refresher publish (see `news_refresher`).
+    // `cutoff` is one past the highest id present at that instant, so replay
+    // owns every id below it and the live stream owns everything from it up —
+    // a clean partition with no duplicate and no gap.
+    let (replay, rx, cutoff) = {
+        let recent = data.recent.read();
+        let rx = data.new_articles.subscribe();
+        let cutoff = data.next_event_id.load(Ordering::Relaxed);
+        let replay: Vec<Result<web::Bytes, actix_web::Error>> = recent
+            .iter()
+            .filter(|(id, _)| last_seen.is_none_or(|seen| *id > seen))
+            .filter(|(_, article)| article.matches_search(&search_term))
+            .map(|(id, article)| Ok(sse_frame(*id, article)))
+            .collect();
+        (replay, rx, cutoff)
+    };
+
+    let live = BroadcastStream::new(rx).filter_map(move |event| {
+        let (id, article) = event.ok()?;
+        // Anything below the cutoff was already covered by the replay.
+        if id < cutoff || !article.matches_search(&search_term) {
+            return None;
+        }
+        Some(Ok::<_, actix_web::Error>(sse_frame(id, &article)))
+    });
+
+    let stream = tokio_stream::iter(replay).chain(live);
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .streaming(stream))
+}
+
+// API endpoint returning the current top trending terms.
+async fn api_trending(data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let trends = trending::top(&data.trending, TRENDING_LIMIT, Utc::now().timestamp());
+    Ok(HttpResponse::Ok().json(trends))
+}
+
+// Compute an ETag for a cache-backed response from the last-updated time, the
+// request's query string, and the hidden-set generation, so it changes whenever
+// the cache is refreshed, the query differs, or a hide/unhide happens. The
+// current-time stamps embedded in rendered pages and feeds are not covered.
+fn cache_etag(last_updated: i64, hidden_gen: u64, query: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    last_updated.hash(&mut hasher);
+    hidden_gen.hash(&mut hasher);
+    query.hash(&mut hasher);
+    format!("\"{:x}\"", hasher.finish())
+}
+
+// Whether the client's `If-None-Match` matches `etag`.
+fn if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    req.headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v == etag)
+        .unwrap_or(false)
+}
+
+// Annotate each article's `hidden` flag from the store and, unless
+// `show_hidden` is set, drop dismissed articles from the result.
+fn apply_hidden(mut articles: Vec<NewsArticle>, store: &HiddenStore, show_hidden: bool) -> Vec<NewsArticle> {
+    for article in &mut articles {
+        article.hidden = store.is_hidden(&article.id);
+    }
+    if show_hidden {
+        articles
+    } else {
+        articles.into_iter().filter(|a| !a.hidden).collect()
+    }
+}
+
+// POST handler dismissing an article by id.
+async fn api_hide(body: web::Json<HideRequest>, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    data.hidden.hide(body.into_inner().id);
+    Ok(HttpResponse::Ok().finish())
+}
+
+// POST handler restoring a previously dismissed article.
+async fn api_unhide(body: web::Json<HideRequest>, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    data.hidden.unhide(&body.into_inner().id);
+    Ok(HttpResponse::Ok().finish())
+}
+
+// Rank the cached articles against the request's `?q=` term and drop dismissed
+// ones, matching the behaviour of `index`/`api_news` so the feed endpoints stay
+// consistent with the HTML and JSON views.
+fn filtered_articles(req: &HttpRequest, data: &AppState) -> Vec<NewsArticle> {
+    let query = web::Query::<SearchQuery>::from_query(req.query_string())
+        .unwrap_or(web::Query(SearchQuery { q: None, show_hidden: None }));
+    let search_term = query.q.clone().unwrap_or_default();
+
+    let ranked = data.search_index.read().search(&search_term);
+    apply_hidden(ranked, &data.hidden, query.show_hidden.unwrap_or(false))
+}
+
+// Build the externally visible base URL (scheme://host) for feed self-links.
+fn base_url(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    format!("{}://{}", info.scheme(), info.host())
+}
+
+// Syndicate the aggregated feed as RSS 2.0.
+async fn feed_rss(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let etag = cache_etag(data.last_updated.load(Ordering::Relaxed), data.hidden.generation(), req.query_string());
+    if if_none_match(&req, &etag) {
+        return Ok(HttpResponse::NotModified().insert_header((header::ETAG, etag)).finish());
+    }
+    let articles = filtered_articles(&req, &data);
+    let body = feed::to_rss(&articles, &base_url(&req), &Utc::now().to_rfc2822());
+    Ok(HttpResponse::Ok()
+        .content_type("application/rss+xml")
+        .insert_header((header::ETAG, etag))
+        .body(body))
+}
+
+// Syndicate the aggregated feed as Atom.
+async fn feed_atom(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let etag = cache_etag(data.last_updated.load(Ordering::Relaxed), data.hidden.generation(), req.query_string());
+    if if_none_match(&req, &etag) {
+        return Ok(HttpResponse::NotModified().insert_header((header::ETAG, etag)).finish());
+    }
+    let articles = filtered_articles(&req, &data);
+    let body = feed::to_atom(&articles, &base_url(&req), &Utc::now().to_rfc2822());
+    Ok(HttpResponse::Ok()
+        .content_type("application/atom+xml")
+        .insert_header((header::ETAG, etag))
+        .body(body))
+}
+
+// Syndicate the aggregated feed as JSON Feed 1.1.
+async fn feed_json(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let etag = cache_etag(data.last_updated.load(Ordering::Relaxed), data.hidden.generation(), req.query_string());
+    if if_none_match(&req, &etag) {
+        return Ok(HttpResponse::NotModified().insert_header((header::ETAG, etag)).finish());
+    }
+    let articles = filtered_articles(&req, &data);
+    let feed_url = format!("{}/feed.json", base_url(&req));
+    let body = feed::to_json_feed(&articles, &feed_url);
+    Ok(HttpResponse::Ok()
+        .content_type("application/feed+json")
+        .insert_header((header::ETAG, etag))
+        .body(body))
 }
 
 #[actix_web::main]
@@ -220,6 +431,13 @@ async fn main() -> std::io::Result<()> {
     // Initialize app state
     let app_state = Arc::new(AppState {
         news_cache: RwLock::new(Vec::new()),
+        search_index: RwLock::new(SearchIndex::default()),
+        trending: RwLock::new(HashMap::new()),
+        new_articles: broadcast::channel(STREAM_CAPACITY).0,
+        next_event_id: AtomicU64::new(0),
+        recent: RwLock::new(VecDeque::new()),
+        hidden: HiddenStore::load(HIDDEN_STORE_PATH),
+        last_updated: AtomicI64::new(0),
         templates: tera,
     });
     
@@ -233,9 +451,23 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(app_state.clone()))
-            .service(web::resource("/").to(index))
-            .service(web::resource("/api/news").to(api_news))
-            .service(Files::new("/static", "./static"))
+            // The SSE endpoint is registered outside the compressed scope: a
+            // generic compressor would buffer the `text/event-stream` response
+            // and add latency to each pushed event.
+            .service(web::resource("/api/stream").to(api_stream))
+            .service(
+                web::scope("")
+                    .wrap(middleware::Compress::default())
+                    .service(web::resource("/").to(index))
+                    .service(web::resource("/api/news").to(api_news))
+                    .service(web::resource("/api/trending").to(api_trending))
+                    .service(web::resource("/api/hide").route(web::post().to(api_hide)))
+                    .service(web::resource("/api/unhide").route(web::post().to(api_unhide)))
+                    .service(web::resource("/feed.rss").to(feed_rss))
+                    .service(web::resource("/feed.atom").to(feed_atom))
+                    .service(web::resource("/feed.json").to(feed_json))
+                    .service(Files::new("/static", "./static")),
+            )
     })
     .bind("127.0.0.1:8080")?
     .run()
@@ -0,0 +1,251 @@
+use crate::NewsArticle;
+use std::collections::{HashMap, HashSet};
+
+// Field weights: a hit in the title counts for more than one in the source,
+// which in turn beats one in the description body.
+const W_TITLE: u16 = 3;
+const W_SOURCE: u16 = 2;
+const W_DESC: u16 = 1;
+
+// A single occurrence of a term in an article.
+struct Posting {
+    article: usize,
+    weight: u16,
+    // Token position within the title (used for proximity ranking); `None`
+    // for source/description hits.
+    title_pos: Option<usize>,
+}
+
+// An inverted index over the current article cache, rebuilt on each refresh.
+// It keeps its own snapshot of the articles it was built from so ranking can
+// never index past a concurrently-swapped cache.
+#[derive(Default)]
+pub struct SearchIndex {
+    postings: HashMap<String, Vec<Posting>>,
+    articles: Vec<NewsArticle>,
+}
+
+impl SearchIndex {
+    // Build the index from the sorted article cache.
+    pub fn build(articles: &[NewsArticle]) -> Self {
+        let mut index = SearchIndex {
+            articles: articles.to_vec(),
+            ..SearchIndex::default()
+        };
+        for (idx, article) in articles.iter().enumerate() {
+            for (pos, token) in tokenize(&article.title).into_iter().enumerate() {
+                index.insert(token, idx, W_TITLE, Some(pos));
+            }
+            for token in tokenize(&article.source) {
+                index.insert(token, idx, W_SOURCE, None);
+            }
+            for token in tokenize(&article.description) {
+                index.insert(token, idx, W_DESC, None);
+            }
+        }
+        index
+    }
+
+    fn insert(&mut self, token: String, article: usize, weight: u16, title_pos: Option<usize>) {
+        self.postings.entry(token).or_default().push(Posting {
+            article,
+            weight,
+            title_pos,
+        });
+    }
+
+    // Rank the indexed articles against `query`, returning matches best-first.
+    // An empty query returns every article in its existing (recency) order.
+    pub fn search(&self, query: &str) -> Vec<NewsArticle> {
+        let articles = &self.articles;
+        let terms = tokenize(query);
+        if terms.is_empty() {
+            return articles.to_vec();
+        }
+
+        let last = terms.len() - 1;
+        let mut scores: HashMap<usize, Score> = HashMap::new();
+
+        for (term_idx, term) in terms.iter().enumerate() {
+            let allow_prefix = term_idx == last;
+            for posting in self.matching_postings(term, allow_prefix) {
+                let entry = scores.entry(posting.article).or_default();
+                entry.terms.insert(term_idx);
+                entry.weight += u32::from(posting.weight);
+                if let Some(pos) = posting.title_pos {
+                    entry.title_positions.push(pos);
+                }
+            }
+        }
+
+        let mut ranked: Vec<(usize, Score)> = scores.into_iter().collect();
+        ranked.sort_by(|(a_idx, a), (b_idx, b)| {
+            // (1) distinct query terms matched
+            b.terms.len().cmp(&a.terms.len())
+                // (2) summed field weights
+                .then(b.weight.cmp(&a.weight))
+                // (3) tighter title proximity
+                .then(a.proximity().cmp(&b.proximity()))
+                // (4) recency
+                .then(articles[*b_idx].timestamp.cmp(&articles[*a_idx].timestamp))
+        });
+
+        ranked.into_iter().map(|(idx, _)| articles[idx].clone()).collect()
+    }
+
+    // All postings whose token matches `term` exactly, within the allowed edit
+    // distance, or (when `allow_prefix`) as a prefix.
+    fn matching_postings(&self, term: &str, allow_prefix: bool) -> impl Iterator<Item = &Posting> {
+        let max_dist = edit_budget(term);
+        self.postings
+            .iter()
+            .filter(move |(token, _)| {
+                *token == term
+                    || (allow_prefix && token.starts_with(term))
+                    || (max_dist > 0 && levenshtein(token, term) <= max_dist)
+            })
+            .flat_map(|(_, postings)| postings.iter())
+    }
+}
+
+// Per-article accumulator used while ranking.
+#[derive(Default)]
+struct Score {
+    terms: HashSet<usize>,
+    weight: u32,
+    title_positions: Vec<usize>,
+}
+
+impl Score {
+    // Span between the first and last matched title token; 0 when fewer than
+    // two title hits. Smaller spans rank higher.
+    fn proximity(&self) -> usize {
+        match (self.title_positions.iter().min(), self.title_positions.iter().max()) {
+            (Some(lo), Some(hi)) => hi - lo,
+            _ => 0,
+        }
+    }
+}
+
+// Edit-distance budget scaled to the query term's length.
+fn edit_budget(term: &str) -> usize {
+    match term.len() {
+        n if n >= 8 => 2,
+        n if n >= 5 => 1,
+        _ => 0,
+    }
+}
+
+// Lowercase, split on non-alphanumerics, drop empties.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+// Classic two-row Levenshtein distance.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    if a.is_empty() {
+        return b.len();
+    }
+    if b.is_empty() {
+        return a.len();
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0usize; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn article(title: &str, description: &str, timestamp: i64) -> NewsArticle {
+        NewsArticle {
+            id: String::new(),
+            title: title.to_string(),
+            link: format!("http://example.com/{}", timestamp),
+            description: description.to_string(),
+            source: "Src".to_string(),
+            pub_date: String::new(),
+            timestamp,
+            hidden: false,
+        }
+    }
+
+    #[test]
+    fn levenshtein_distances() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+        assert_eq!(levenshtein("abc", "abc"), 0);
+        assert_eq!(levenshtein("", "abc"), 3);
+    }
+
+    #[test]
+    fn edit_budget_scales_with_length() {
+        assert_eq!(edit_budget("abcd"), 0);
+        assert_eq!(edit_budget("abcde"), 1);
+        assert_eq!(edit_budget("abcdefgh"), 2);
+    }
+
+    #[test]
+    fn tolerates_typos_within_budget() {
+        let arts = vec![article("Bitcoin rallies", "", 1)];
+        let index = SearchIndex::build(&arts);
+        // "bitcon" is one edit from "bitcoin" (length 6 -> budget 1).
+        assert_eq!(index.search("bitcon").len(), 1);
+    }
+
+    #[test]
+    fn prefix_matches_on_final_term() {
+        let arts = vec![article("Bitcoin surges", "", 1)];
+        let index = SearchIndex::build(&arts);
+        // "bitc" is too short for an edit budget but matches as a prefix.
+        assert_eq!(index.search("bitc").len(), 1);
+    }
+
+    #[test]
+    fn ranks_more_matched_terms_first() {
+        let arts = vec![
+            article("crypto news today", "", 1),
+            article("crypto market update", "", 2),
+        ];
+        let index = SearchIndex::build(&arts);
+        let results = index.search("crypto news");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "crypto news today");
+    }
+
+    #[test]
+    fn ranks_title_weight_above_description() {
+        let arts = vec![
+            article("ethereum rises", "markets calm", 1),
+            article("markets open", "ethereum dips", 2),
+        ];
+        let index = SearchIndex::build(&arts);
+        let results = index.search("ethereum");
+        assert_eq!(results.len(), 2);
+        // Both match one term, so the heavier title hit outranks recency.
+        assert_eq!(results[0].title, "ethereum rises");
+    }
+
+    #[test]
+    fn empty_query_returns_everything() {
+        let arts = vec![article("a", "", 1), article("b", "", 2)];
+        let index = SearchIndex::build(&arts);
+        assert_eq!(index.search("").len(), 2);
+    }
+}
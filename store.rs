@@ -0,0 +1,70 @@
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// A small on-disk store of dismissed article ids (the SHA of each link). The
+// set is loaded at startup and written back through after every change so the
+// curated view survives restarts.
+pub struct HiddenStore {
+    path: PathBuf,
+    ids: RwLock<HashSet<String>>,
+    // Bumped on every change so cache-backed responses can invalidate their
+    // ETags the moment the hidden set is mutated.
+    generation: AtomicU64,
+}
+
+impl HiddenStore {
+    // Load the hidden set from `path`, starting empty if the file is missing
+    // or unreadable.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let ids = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|raw| serde_json::from_str::<HashSet<String>>(&raw).ok())
+            .unwrap_or_default();
+        HiddenStore {
+            path,
+            ids: RwLock::new(ids),
+            generation: AtomicU64::new(0),
+        }
+    }
+
+    // Whether an article id is currently dismissed.
+    pub fn is_hidden(&self, id: &str) -> bool {
+        self.ids.read().contains(id)
+    }
+
+    // A value that changes whenever the hidden set is mutated.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    // Mark an article id as dismissed and persist the change.
+    pub fn hide(&self, id: String) {
+        if self.ids.write().insert(id) {
+            self.generation.fetch_add(1, Ordering::Relaxed);
+            self.persist();
+        }
+    }
+
+    // Restore a dismissed article id and persist the change.
+    pub fn unhide(&self, id: &str) {
+        if self.ids.write().remove(id) {
+            self.generation.fetch_add(1, Ordering::Relaxed);
+            self.persist();
+        }
+    }
+
+    // Write the current set to disk, logging but not propagating IO errors.
+    fn persist(&self) {
+        if let Err(e) = write_set(&self.path, &self.ids.read()) {
+            eprintln!("Failed to persist hidden store: {}", e);
+        }
+    }
+}
+
+fn write_set(path: &Path, ids: &HashSet<String>) -> std::io::Result<()> {
+    let json = serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string());
+    std::fs::write(path, json)
+}
@@ -0,0 +1,114 @@
+use crate::NewsArticle;
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Keyword hits are accumulated in 15-minute buckets over a 24-hour horizon.
+const BUCKET_SECS: i64 = 15 * 60;
+const HORIZON_SECS: i64 = 24 * 60 * 60;
+// Decay constant chosen so a hit loses half its weight after ~6 hours.
+const HALF_LIFE_SECS: f64 = 6.0 * 60.0 * 60.0;
+
+// The per-token time-bucketed hit counts, keyed by token and ordered oldest
+// first. Lives behind a `RwLock` inside `AppState`.
+pub type Buckets = RwLock<HashMap<String, VecDeque<(i64, u32)>>>;
+
+// Very small English stopword list; enough to keep the trend list meaningful
+// without pulling in a dependency.
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "are", "but", "not", "you", "all", "any", "can", "has",
+    "her", "was", "one", "our", "out", "his", "how", "its", "new", "now", "who",
+    "will", "with", "this", "that", "from", "have", "they", "your", "what",
+    "been", "more", "were", "into", "than", "then", "them", "some", "such",
+    "about", "after", "over", "said", "says",
+];
+
+// Short ticker symbols that are always kept, bypassing the length and
+// stopword filters.
+const TICKERS: &[&str] = &["btc", "eth", "xrp", "bnb", "sol", "ada", "doge"];
+
+// Tokenize an article's title and description into distinct, normalized terms.
+fn tokens(article: &NewsArticle) -> HashSet<String> {
+    let stop: HashSet<&str> = STOPWORDS.iter().copied().collect();
+    let tickers: HashSet<&str> = TICKERS.iter().copied().collect();
+
+    let text = format!("{} {}", article.title, article.description);
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|w| w.to_lowercase())
+        .filter(|w| {
+            if tickers.contains(w.as_str()) {
+                return true;
+            }
+            w.len() >= 3 && !stop.contains(w.as_str())
+        })
+        .collect()
+}
+
+// Fold a freshly fetched batch into the buckets: record, at `now`, one hit per
+// article containing each token, skip tokens seen in only a single article,
+// and evict stale buckets.
+pub fn update(buckets: &Buckets, articles: &[NewsArticle], now: i64) {
+    // Document frequency across this batch so single-article noise is dropped.
+    let mut doc_freq: HashMap<String, u32> = HashMap::new();
+    for article in articles {
+        for token in tokens(article) {
+            *doc_freq.entry(token).or_insert(0) += 1;
+        }
+    }
+
+    let bucket_ts = now - now.rem_euclid(BUCKET_SECS);
+    let cutoff = now - HORIZON_SECS;
+    let mut map = buckets.write();
+
+    for (token, count) in doc_freq {
+        if count < 2 {
+            continue;
+        }
+        let series = map.entry(token).or_default();
+        match series.back_mut() {
+            Some((ts, c)) if *ts == bucket_ts => *c += count,
+            _ => series.push_back((bucket_ts, count)),
+        }
+    }
+
+    // Evict buckets older than the horizon and forget tokens that fall empty.
+    map.retain(|_, series| {
+        while series.front().is_some_and(|(ts, _)| *ts < cutoff) {
+            series.pop_front();
+        }
+        !series.is_empty()
+    });
+}
+
+// A trending token and its decayed score, for the API and sidebar.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Trend {
+    pub term: String,
+    pub score: f64,
+}
+
+// Rank tokens by exponentially time-decayed hit counts and return the top `n`.
+pub fn top(buckets: &Buckets, n: usize, now: i64) -> Vec<Trend> {
+    let lambda = std::f64::consts::LN_2 / HALF_LIFE_SECS;
+    let mut trends: Vec<Trend> = buckets
+        .read()
+        .iter()
+        .map(|(term, series)| {
+            let score = series
+                .iter()
+                .map(|(ts, count)| {
+                    let age = (now - ts) as f64;
+                    f64::from(*count) * (-lambda * age).exp()
+                })
+                .sum();
+            Trend {
+                term: term.clone(),
+                score,
+            }
+        })
+        .filter(|t| t.score > 0.0)
+        .collect();
+
+    trends.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    trends.truncate(n);
+    trends
+}